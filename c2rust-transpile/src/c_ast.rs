@@ -0,0 +1,290 @@
+//! A typed, arena-indexed model of the Clang AST that sits between the
+//! exported C translation unit and the `rust_ast` we eventually print.
+//!
+//! Every node is referenced by a small newtype id so that `TypedAstContext`
+//! can own all of the nodes in `IndexMap`s without fighting the borrow
+//! checker; traversals look nodes up by id rather than holding references
+//! into the tree.
+
+use indexmap::IndexMap;
+
+macro_rules! newtype_id {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(pub u64);
+    };
+}
+
+newtype_id!(
+    /// Identifies a `CTypeKind` stored in a `TypedAstContext`.
+    CTypeId
+);
+newtype_id!(
+    /// Identifies a `CExprKind`.
+    CExprId
+);
+newtype_id!(
+    /// Identifies a `CDeclKind` (functions, variables, records, ...).
+    CDeclId
+);
+newtype_id!(
+    /// Identifies a `CStmtKind`.
+    CStmtId
+);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Qualifiers {
+    pub is_const: bool,
+    pub is_restrict: bool,
+    pub is_volatile: bool,
+}
+
+/// A C type together with the qualifiers that were written on it at this
+/// particular use (as opposed to the qualifiers baked into the type itself).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CQualTypeId {
+    pub qualifiers: Qualifiers,
+    pub ctype: CTypeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CTypeKind {
+    Void,
+    Bool,
+    Char,
+    Int,
+    Long,
+    ULong,
+    Double,
+    Pointer(CQualTypeId),
+    /// A C `struct`/`union` tag. `is_complete` is false for the classic
+    /// opaque-handle pattern (`typedef struct HDEV *HDEV;` with no matching
+    /// `struct HDEV { ... };` anywhere in the translation unit) and drives
+    /// `translator`/`convert_type` to emit an `extern { pub type Foo; }`
+    /// item instead of a zero-sized struct or an untyped pointer.
+    Record {
+        name: Option<String>,
+        is_complete: bool,
+    },
+    /// `ret(params...)`, optionally consuming a C `...` vararg tail.
+    ///
+    /// `is_variadic` is set for prototypes declared with a trailing `...`,
+    /// e.g. `int printf(const char *fmt, ...);`. Translating the
+    /// declaration emits the same trailing `...` in the generated
+    /// `rust_ast` foreign-fn signature; translating a *definition* that
+    /// uses `<stdarg.h>` additionally drives the `va_list` lowering in
+    /// `translator::Translation::convert_function`.
+    Function {
+        ret: CQualTypeId,
+        params: Vec<CQualTypeId>,
+        is_variadic: bool,
+        is_noreturn: bool,
+    },
+}
+
+/// A C expression. Of the `<stdarg.h>` macros, only `va_arg` has a
+/// dedicated variant (Clang's `VAArgExpr`); `va_start`/`va_end`/`va_copy`
+/// stay ordinary `Call`s to their builtins, which `translator` recognizes
+/// by the callee's name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CExprKind {
+    Literal(CQualTypeId),
+    DeclRef(CDeclId),
+    Call(CDeclId, Vec<CExprId>),
+    /// `va_arg(ap, ty)`
+    VAArg { va_list: CExprId, ty: CQualTypeId },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CStmtKind {
+    Expr(CExprId),
+    Compound(Vec<CStmtId>),
+    Return(Option<CExprId>),
+}
+
+#[derive(Debug, Clone)]
+pub enum CDeclKind {
+    Variable {
+        name: String,
+        typ: CQualTypeId,
+        /// Set for a top-level `extern int x;`/`extern const char *name;`
+        /// with no initializer in this translation unit — these lower to
+        /// `static`/`static mut` items inside an `extern "C" { ... }`
+        /// block rather than a local Rust `static`, since the storage is
+        /// actually defined elsewhere and linked in.
+        is_extern: bool,
+    },
+    Function {
+        name: String,
+        typ: CTypeId,
+        /// Mirrors `CTypeKind::Function::is_variadic` for convenience so
+        /// callers don't have to chase through `typ` to find out whether
+        /// this definition needs a trailing `VaListImpl` parameter.
+        is_variadic: bool,
+        parameters: Vec<CDeclId>,
+        body: Option<CStmtId>,
+    },
+}
+
+/// A 1-based source position, as reported by `c2rust_ast_exporter` from
+/// Clang's `SourceManager`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SrcPos {
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CommentKind {
+    /// `// ...`
+    Line,
+    /// `/* ... */`
+    Block,
+}
+
+/// A single C comment as exported by `c2rust_ast_exporter`, kept around
+/// only long enough for `translator` to attach it to the `rust_ast` node it
+/// sits next to; `TypedAstContext` matches comments to declarations only by
+/// line and column (see `leading_comment`/`trailing_comment`).
+#[derive(Debug, Clone)]
+pub struct CComment {
+    pub kind: CommentKind,
+    pub start: SrcPos,
+    pub end: SrcPos,
+    pub text: String,
+}
+
+/// Owns every node produced while importing a C translation unit, each
+/// keyed by the small id types above so the rest of the pipeline can cheaply
+/// clone a reference to a node without cloning the node itself.
+#[derive(Debug, Default)]
+pub struct TypedAstContext {
+    pub types: IndexMap<CTypeId, CTypeKind>,
+    pub exprs: IndexMap<CExprId, CExprKind>,
+    pub decls: IndexMap<CDeclId, CDeclKind>,
+    pub stmts: IndexMap<CStmtId, CStmtKind>,
+    /// Where each top-level decl starts in the original source, used only
+    /// to figure out which comment (if any) immediately precedes or
+    /// trails it. Populated by `c2rust_ast_exporter` alongside `decls`.
+    pub decl_locs: IndexMap<CDeclId, SrcPos>,
+    /// Every comment in the translation unit, in source order. Empty
+    /// unless the exporter was asked to record them (`--preserve-comments`
+    /// controls whether `translator` bothers consulting this at all).
+    pub comments: Vec<CComment>,
+}
+
+impl TypedAstContext {
+    pub fn new() -> Self {
+        TypedAstContext::default()
+    }
+
+    pub fn resolve_type(&self, id: CTypeId) -> &CTypeKind {
+        &self.types[&id]
+    }
+
+    pub fn resolve_decl(&self, id: CDeclId) -> &CDeclKind {
+        &self.decls[&id]
+    }
+
+    /// True when `decl` is a function whose `CDeclKind::Function::is_variadic`
+    /// flag is set; the function's `CTypeKind` is not consulted.
+    pub fn is_variadic_function(&self, decl: CDeclId) -> bool {
+        match self.resolve_decl(decl) {
+            CDeclKind::Function { is_variadic, .. } => *is_variadic,
+            _ => false,
+        }
+    }
+
+    /// True when `comment` trails *some* declaration on the same line
+    /// (`int x; // comm 2`), regardless of which decl `trailing_comment`
+    /// is being asked about. A comment on the line directly above the
+    /// next declaration is ambiguous — it could be read as either that
+    /// decl's leading comment, or as the previous decl's trailing
+    /// comment — and a same-line trailing placement is the more specific
+    /// reading, so `leading_comment` defers to it via this check.
+    fn is_anyones_trailing_comment(&self, comment: &CComment) -> bool {
+        self.decl_locs
+            .values()
+            .any(|loc| loc.line == comment.start.line && comment.start.column > loc.column)
+    }
+
+    /// The comment, if any, that ends on the line immediately above
+    /// `decl`'s own line — e.g. `// comment 1` directly above a
+    /// declaration. Becomes an item-preceding comment in `translator`.
+    /// Skips a comment that's already claimed as some other decl's
+    /// trailing comment, so a one-line gap between two declarations never
+    /// attaches the same comment to both.
+    pub fn leading_comment(&self, decl: CDeclId) -> Option<&CComment> {
+        let loc = self.decl_locs.get(&decl)?;
+        self.comments
+            .iter()
+            .find(|c| c.end.line + 1 == loc.line && !self.is_anyones_trailing_comment(c))
+    }
+
+    /// The comment, if any, that starts on the same line `decl` does —
+    /// e.g. `int x; // comm 2` trailing a declaration on one line.
+    /// Becomes an end-of-line comment in `translator`.
+    pub fn trailing_comment(&self, decl: CDeclId) -> Option<&CComment> {
+        let loc = self.decl_locs.get(&decl)?;
+        self.comments
+            .iter()
+            .find(|c| c.start.line == loc.line && c.start.column > loc.column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_comment(line: u32, start_col: u32, end_col: u32, text: &str) -> CComment {
+        CComment {
+            kind: CommentKind::Line,
+            start: SrcPos { line, column: start_col },
+            end: SrcPos { line, column: end_col },
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn leading_comment_attaches_to_the_next_decl() {
+        let mut ctxt = TypedAstContext::new();
+        let b = CDeclId(2);
+        ctxt.decl_locs.insert(b, SrcPos { line: 3, column: 1 });
+        ctxt.comments.push(line_comment(2, 1, 20, "// comment 1"));
+
+        assert_eq!(ctxt.leading_comment(b).map(|c| c.text.as_str()), Some("// comment 1"));
+    }
+
+    #[test]
+    fn same_line_trailing_comment_does_not_double_as_the_next_decls_leading_comment() {
+        // int a; // comm 2
+        // int b;
+        let mut ctxt = TypedAstContext::new();
+        let a = CDeclId(1);
+        let b = CDeclId(2);
+        ctxt.decl_locs.insert(a, SrcPos { line: 1, column: 1 });
+        ctxt.decl_locs.insert(b, SrcPos { line: 2, column: 1 });
+        ctxt.comments.push(line_comment(1, 8, 20, "// comm 2"));
+
+        assert_eq!(ctxt.trailing_comment(a).map(|c| c.text.as_str()), Some("// comm 2"));
+        assert_eq!(ctxt.leading_comment(b), None);
+    }
+
+    #[test]
+    fn leading_comment_on_its_own_line_is_unaffected_by_trailing_logic() {
+        // // comment 1
+        // int a;
+        // int b; // comm 2
+        let mut ctxt = TypedAstContext::new();
+        let a = CDeclId(1);
+        let b = CDeclId(2);
+        ctxt.decl_locs.insert(a, SrcPos { line: 2, column: 1 });
+        ctxt.decl_locs.insert(b, SrcPos { line: 3, column: 1 });
+        ctxt.comments.push(line_comment(1, 1, 13, "// comment 1"));
+        ctxt.comments.push(line_comment(3, 8, 17, "// comm 2"));
+
+        assert_eq!(ctxt.leading_comment(a).map(|c| c.text.as_str()), Some("// comment 1"));
+        assert_eq!(ctxt.trailing_comment(b).map(|c| c.text.as_str()), Some("// comm 2"));
+    }
+}
@@ -0,0 +1,20 @@
+//! Control-flow graph used to translate arbitrary C `goto`s into structured
+//! Rust control flow. Not exercised by the straight-line translations in
+//! this crate yet, but kept as its own module since `translator` will need
+//! to hand off any function containing a `goto` to a `Cfg` built here
+//! rather than translating its body statement-by-statement.
+
+use crate::c_ast::CStmtId;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BasicBlockId(pub u64);
+
+pub struct BasicBlock {
+    pub body: Vec<CStmtId>,
+    pub successors: Vec<BasicBlockId>,
+}
+
+pub struct Cfg {
+    pub entry: BasicBlockId,
+    pub blocks: Vec<(BasicBlockId, BasicBlock)>,
+}
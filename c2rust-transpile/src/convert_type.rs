@@ -0,0 +1,150 @@
+//! Lowers `c_ast::CTypeKind` into the `syntax::ast::Ty` nodes that make up
+//! the generated Rust signatures.
+
+use c2rust_ast_builder::mk;
+use syntax::ast;
+use syntax::ptr::P;
+
+use crate::c_ast::{CQualTypeId, CTypeId, CTypeKind, TypedAstContext};
+use crate::renamer::Renamer;
+use crate::rust_ast::mk_foreign_fn_decl;
+
+pub struct TypeConverter {
+    /// Dedupes the names handed out to incomplete records so two distinct
+    /// opaque handles that happen to share a C tag (e.g. both declared
+    /// `struct Opaque;` in unrelated headers pulled into the same
+    /// translation unit) don't collide on the same generated `extern type`.
+    record_names: Renamer<CTypeId>,
+}
+
+impl TypeConverter {
+    pub fn new() -> Self {
+        TypeConverter {
+            record_names: Renamer::new(Default::default()),
+        }
+    }
+
+    pub fn convert(&mut self, ctxt: &TypedAstContext, ctype: CTypeId) -> P<ast::Ty> {
+        match ctxt.resolve_type(ctype) {
+            CTypeKind::Void => mk().tuple_ty(vec![] as Vec<P<ast::Ty>>),
+            CTypeKind::Bool => mk().path_ty(vec!["bool"]),
+            CTypeKind::Char => mk().path_ty(vec!["libc", "c_char"]),
+            CTypeKind::Int => mk().path_ty(vec!["libc", "c_int"]),
+            CTypeKind::Long => mk().path_ty(vec!["libc", "c_long"]),
+            CTypeKind::ULong => mk().path_ty(vec!["libc", "c_ulong"]),
+            CTypeKind::Double => mk().path_ty(vec!["libc", "c_double"]),
+            CTypeKind::Pointer(qual) => {
+                let pointee = self.convert(ctxt, qual.ctype);
+                if qual.qualifiers.is_const {
+                    mk().set_mutbl(ast::Mutability::Immutable).ptr_ty(pointee)
+                } else {
+                    mk().set_mutbl(ast::Mutability::Mutable).ptr_ty(pointee)
+                }
+            }
+            CTypeKind::Record { is_complete: false, .. } => {
+                mk().path_ty(vec![self.record_extern_type_name(ctxt, ctype)])
+            }
+            CTypeKind::Record { name, .. } => {
+                // A *complete* record still has real fields somewhere in
+                // the translation unit; this crate doesn't lower struct
+                // bodies yet; fall back to its tag so the reference is at
+                // least traceable back to the C declaration.
+                mk().path_ty(vec![name.clone().unwrap_or_else(|| "Unnamed".to_string())])
+            }
+            CTypeKind::Function { .. } => {
+                // Bare function *types* (as opposed to the foreign-fn
+                // *items* built in `translator`) aren't needed by this
+                // crate yet; treat them as an opaque unit until a caller
+                // requires otherwise.
+                mk().tuple_ty(vec![] as Vec<P<ast::Ty>>)
+            }
+        }
+    }
+
+    /// Name used both for the `extern { pub type Name; }` item and for any
+    /// `*mut Name`/`*const Name` pointer that targets this incomplete
+    /// record, reserved once per `CTypeId` so repeated lookups agree.
+    pub fn record_extern_type_name(&mut self, ctxt: &TypedAstContext, ctype: CTypeId) -> String {
+        if let Some(existing) = self.record_names.get(&ctype) {
+            return existing;
+        }
+        let wanted = match ctxt.resolve_type(ctype) {
+            CTypeKind::Record { name: Some(name), .. } => name.clone(),
+            _ => "Opaque".to_string(),
+        };
+        self.record_names.insert(ctype, &wanted)
+    }
+
+    /// Builds the `fn(params...) -> ret` declaration for a foreign
+    /// prototype, carrying through `is_variadic` so a C `...` prototype
+    /// round-trips to a Rust `...` foreign-fn signature instead of being
+    /// silently truncated to its named parameters.
+    pub fn convert_foreign_fn_decl(
+        &mut self,
+        ctxt: &TypedAstContext,
+        ret: CQualTypeId,
+        params: &[CQualTypeId],
+        param_names: &[String],
+        is_variadic: bool,
+    ) -> P<ast::FnDecl> {
+        let args = params
+            .iter()
+            .zip(param_names.iter())
+            .map(|(param, name)| {
+                let ty = self.convert(ctxt, param.ctype);
+                mk().arg(ty, name.as_str())
+            })
+            .collect();
+
+        let ret_ty = match ctxt.resolve_type(ret.ctype) {
+            CTypeKind::Void => ast::FunctionRetTy::Default(syntax_pos::DUMMY_SP),
+            _ => ast::FunctionRetTy::Ty(self.convert(ctxt, ret.ctype)),
+        };
+
+        mk_foreign_fn_decl(args, ret_ty, is_variadic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opaque_record(ctxt: &mut TypedAstContext, id: u64, name: &str) -> CTypeId {
+        let ctype = CTypeId(id);
+        ctxt.types.insert(
+            ctype,
+            CTypeKind::Record {
+                name: Some(name.to_string()),
+                is_complete: false,
+            },
+        );
+        ctype
+    }
+
+    #[test]
+    fn record_extern_type_name_is_memoized_per_ctype() {
+        let mut ctxt = TypedAstContext::new();
+        let handle = opaque_record(&mut ctxt, 1, "Opaque");
+        let mut converter = TypeConverter::new();
+
+        let first = converter.record_extern_type_name(&ctxt, handle);
+        let second = converter.record_extern_type_name(&ctxt, handle);
+
+        assert_eq!(first, "Opaque");
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn record_extern_type_name_dedupes_distinct_records_sharing_a_tag() {
+        let mut ctxt = TypedAstContext::new();
+        let a = opaque_record(&mut ctxt, 1, "Opaque");
+        let b = opaque_record(&mut ctxt, 2, "Opaque");
+        let mut converter = TypeConverter::new();
+
+        let a_name = converter.record_extern_type_name(&ctxt, a);
+        let b_name = converter.record_extern_type_name(&ctxt, b);
+
+        assert_eq!(a_name, "Opaque");
+        assert_ne!(a_name, b_name, "two distinct opaque records must not share a generated name");
+    }
+}
@@ -0,0 +1,73 @@
+//! Picks collision-free Rust identifiers for C names as they come into
+//! scope, and hands back the same identifier for the same C entity for as
+//! long as that scope is live.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// A simple block-scoped renaming table. `T` is whatever key identifies the
+/// C entity being renamed (a `CDeclId`, a type name, ...).
+pub struct Renamer<T: Eq + Hash + Clone> {
+    scopes: Vec<HashMap<T, String>>,
+    used_names: Vec<HashSet<String>>,
+    keywords: HashSet<String>,
+}
+
+impl<T: Eq + Hash + Clone> Renamer<T> {
+    pub fn new(keywords: HashSet<String>) -> Self {
+        Renamer {
+            scopes: vec![HashMap::new()],
+            used_names: vec![HashSet::new()],
+            keywords,
+        }
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+        self.used_names.push(HashSet::new());
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+        self.used_names.pop();
+    }
+
+    pub fn get(&self, key: &T) -> Option<String> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(key).cloned())
+    }
+
+    fn is_taken(&self, name: &str) -> bool {
+        self.keywords.contains(name) || self.used_names.iter().any(|used| used.contains(name))
+    }
+
+    /// Reserve `wanted` for `key`, appending a numeric suffix until the
+    /// name is free in every live scope (and isn't a Rust keyword).
+    pub fn insert(&mut self, key: T, wanted: &str) -> String {
+        let mut name = wanted.to_string();
+        let mut suffix = 0u32;
+        while self.is_taken(&name) {
+            suffix += 1;
+            name = format!("{}_{}", wanted, suffix);
+        }
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(key, name.clone());
+        self.used_names.last_mut().unwrap().insert(name.clone());
+        name
+    }
+
+    /// Marks `name` as used in the innermost scope, as is, without binding
+    /// it to any key, so that a later `insert` for a colliding C
+    /// declaration will not pick it. Used
+    /// for synthesized top-level items (e.g. an `extern type` generated to
+    /// stand in for an opaque record) that have no `CDeclId` of their own
+    /// to key off of but still need to occupy the namespace.
+    pub fn reserve(&mut self, name: &str) {
+        self.used_names.last_mut().unwrap().insert(name.to_string());
+    }
+}
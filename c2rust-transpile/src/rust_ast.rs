@@ -0,0 +1,88 @@
+//! Small helpers for assembling pieces of Rust AST that `c2rust_ast_builder`
+//! doesn't hand us directly, layered on top of its `mk()` builder.
+
+use std::collections::HashMap;
+
+use c2rust_ast_builder::mk;
+use syntax::ast;
+use syntax::ptr::P;
+use syntax_pos::symbol::Ident;
+
+/// Comments have no place in `syntax::ast` itself — they're source trivia,
+/// not tokens — so they're carried alongside the tree keyed by the
+/// `NodeId` of the item they were attached to, for whatever pretty-printer
+/// drives the final output to consult (mirroring how `syntax::print::pprust`
+/// takes an out-of-band comment list keyed by position).
+#[derive(Debug, Default)]
+pub struct CommentMap {
+    /// A comment on its own line(s) directly above an item.
+    pub leading: HashMap<ast::NodeId, String>,
+    /// A comment on the same line as, and after, an item.
+    pub trailing: HashMap<ast::NodeId, String>,
+}
+
+impl CommentMap {
+    pub fn new() -> Self {
+        CommentMap::default()
+    }
+
+    pub fn add_leading(&mut self, node: ast::NodeId, text: String) {
+        self.leading.insert(node, text);
+    }
+
+    pub fn add_trailing(&mut self, node: ast::NodeId, text: String) {
+        self.trailing.insert(node, text);
+    }
+}
+
+/// Builds the `fn(params...) -> ret` signature for an `extern` foreign-fn
+/// item, optionally trailing it with the C `...` vararg marker so it
+/// round-trips as e.g. `fn printf(fmt: *const c_char, ...) -> c_int;`.
+pub fn mk_foreign_fn_decl(
+    params: Vec<ast::Arg>,
+    ret: ast::FunctionRetTy,
+    is_variadic: bool,
+) -> P<ast::FnDecl> {
+    P(ast::FnDecl {
+        inputs: params,
+        output: ret,
+        variadic: is_variadic,
+    })
+}
+
+pub fn mk_foreign_fn_item(
+    name: &str,
+    decl: P<ast::FnDecl>,
+    generics: ast::Generics,
+) -> ast::ForeignItem {
+    mk().foreign_fn_item(Ident::from_str(name), decl, generics)
+}
+
+/// `pub type Name;`, the `extern_types` foreign item for an opaque,
+/// never-completed C record; the caller wraps it in an `extern { ... }`
+/// block.
+pub fn mk_extern_type_item(name: &str) -> ast::ForeignItem {
+    mk().pub_().foreign_ty_item(Ident::from_str(name))
+}
+
+/// `static NAME: T;` (immutable) or `static mut NAME: T;` (mutable) inside
+/// an `extern "C" { ... }` block, mirroring a C `extern` global whose
+/// mutability comes from whether the C declaration was `const`-qualified.
+pub fn mk_extern_static_item(name: &str, ty: P<ast::Ty>, is_mut: bool) -> ast::ForeignItem {
+    let builder = mk().pub_();
+    if is_mut {
+        builder.set_mutbl(ast::Mutability::Mutable).foreign_static_item(Ident::from_str(name), ty)
+    } else {
+        builder.set_mutbl(ast::Mutability::Immutable).foreign_static_item(Ident::from_str(name), ty)
+    }
+}
+
+/// `#![feature(name)]`, used to gate on demand for `c_variadic`,
+/// `extern_types`, and similar unstable features that only a handful of
+/// translated programs actually need.
+pub fn mk_feature_attr(name: &str) -> ast::Attribute {
+    mk().inner().call_attr(
+        "feature",
+        vec![name],
+    ).attribute_of_kind(ast::AttrStyle::Inner)
+}
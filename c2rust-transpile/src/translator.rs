@@ -0,0 +1,699 @@
+//! Walks a `TypedAstContext` and produces the top-level `syntax::ast` items
+//! that make up the translated Rust module.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use c2rust_ast_builder::mk;
+use syntax::ast;
+use syntax::ptr::P;
+use syntax_pos::symbol::Ident;
+
+use crate::c_ast::{CDeclId, CDeclKind, CExprId, CExprKind, CTypeId, CTypeKind, TypedAstContext};
+use crate::convert_type::TypeConverter;
+use crate::renamer::Renamer;
+use crate::rust_ast::{
+    mk_extern_static_item, mk_extern_type_item, mk_feature_attr, mk_foreign_fn_item, CommentMap,
+};
+
+/// Knobs that change how a `TypedAstContext` gets lowered, analogous to the
+/// CLI flags on the `c2rust transpile` front end.
+#[derive(Debug, Clone, Default)]
+pub struct TranslationConfig {
+    /// Re-attach C comments to the nearest generated item. Off by default
+    /// because re-threading comments back onto synthesized spans can
+    /// perturb the pretty-printer's formatting decisions.
+    pub preserve_comments: bool,
+}
+
+pub struct Translation<'c> {
+    pub ast_context: &'c TypedAstContext,
+    pub config: TranslationConfig,
+    /// Shared across every `convert_*` call so that a record named once
+    /// (by a function signature referencing it, or by `convert_opaque_records`
+    /// itself) keeps the same generated name everywhere else it's used.
+    pub type_converter: RefCell<TypeConverter>,
+    /// `RefCell`-wrapped so every `convert_*` method — all of which take
+    /// `&self`, matching `type_converter` — can reserve a name as it goes
+    /// rather than requiring a `&mut Translation` driver loop.
+    pub renamer: RefCell<Renamer<CDeclId>>,
+    /// Populated only when `config.preserve_comments` is set.
+    pub comment_map: RefCell<CommentMap>,
+    /// Maps the `CDeclId` of a `va_list`-typed local to the Rust binding
+    /// standing in for it — the real `args: VaListImpl` parameter for a
+    /// local that's only ever been `va_start`-ed, or a freshly minted
+    /// local for one produced by `va_copy`. Keyed per-function; callers
+    /// translating more than one variadic definition should give each its
+    /// own `Translation` or otherwise clear this between functions.
+    va_list_bindings: RefCell<HashMap<CDeclId, String>>,
+    next_node_id: Cell<u32>,
+    /// Tracks which unstable features a translated function has forced us
+    /// to rely on, so the crate-level `#![feature(...)]` attributes emitted
+    /// by `crate_features` only ever name what was actually used.
+    uses_variadic: Cell<bool>,
+    uses_extern_types: Cell<bool>,
+}
+
+impl<'c> Translation<'c> {
+    pub fn new(ast_context: &'c TypedAstContext, config: TranslationConfig) -> Self {
+        Translation {
+            ast_context,
+            config,
+            type_converter: RefCell::new(TypeConverter::new()),
+            renamer: RefCell::new(Renamer::new(Default::default())),
+            comment_map: RefCell::new(CommentMap::new()),
+            va_list_bindings: RefCell::new(HashMap::new()),
+            next_node_id: Cell::new(1),
+            uses_variadic: Cell::new(false),
+            uses_extern_types: Cell::new(false),
+        }
+    }
+
+    fn fresh_node_id(&self) -> ast::NodeId {
+        let id = self.next_node_id.get();
+        self.next_node_id.set(id + 1);
+        ast::NodeId::from_u32(id)
+    }
+
+    /// When `--preserve-comments` is on, gives `item` a real `NodeId` and
+    /// records whichever of `decl_id`'s leading/trailing comments exist
+    /// into `comment_map`, keyed by that id — leading comments become
+    /// item-preceding comments, same-line trailing comments become
+    /// end-of-line comments. A no-op (and `item` keeps `DUMMY_NODE_ID`)
+    /// when the toggle is off, since nothing will ever consult the map.
+    fn attach_comments(&self, decl_id: CDeclId, mut item: ast::Item) -> ast::Item {
+        if !self.config.preserve_comments {
+            return item;
+        }
+        let leading = self.ast_context.leading_comment(decl_id).cloned();
+        let trailing = self.ast_context.trailing_comment(decl_id).cloned();
+        if leading.is_none() && trailing.is_none() {
+            return item;
+        }
+        let node_id = self.fresh_node_id();
+        item.id = node_id;
+        let mut comments = self.comment_map.borrow_mut();
+        if let Some(c) = leading {
+            comments.add_leading(node_id, c.text);
+        }
+        if let Some(c) = trailing {
+            comments.add_trailing(node_id, c.text);
+        }
+        item
+    }
+
+    /// Emits `extern { pub type Name; }` for every forward-declared
+    /// struct/union that is never completed anywhere in the translation
+    /// unit — the opaque-handle pattern. Pointers elsewhere in the output
+    /// that target one of these (built by `convert_type::TypeConverter`)
+    /// reference the same generated name, so identity and `size_of`
+    /// opacity both fall out of using a genuine `extern type` rather than
+    /// a zero-sized struct or an untyped pointer.
+    ///
+    /// Each record's name is also reserved in `self.renamer` — the same
+    /// table `convert_function`/`convert_extern_globals` insert into — so
+    /// a function or global translated afterwards whose C name collides
+    /// with a record (e.g. `extern struct Foo *get(void);` alongside
+    /// `extern int Foo;`) comes out suffixed instead of silently sharing
+    /// the record's top-level identifier. As with that caveat on
+    /// `convert_extern_globals`, this only catches collisions against
+    /// items translated *after* this call — callers should translate
+    /// opaque records first.
+    pub fn convert_opaque_records(&self) -> Vec<ast::Item> {
+        let mut items = vec![];
+        for (&ctype_id, kind) in &self.ast_context.types {
+            if let CTypeKind::Record {
+                is_complete: false, ..
+            } = kind
+            {
+                items.push(self.convert_opaque_record(ctype_id));
+            }
+        }
+        if !items.is_empty() {
+            self.uses_extern_types.set(true);
+        }
+        items
+    }
+
+    fn convert_opaque_record(&self, ctype_id: CTypeId) -> ast::Item {
+        let name = self
+            .type_converter
+            .borrow_mut()
+            .record_extern_type_name(self.ast_context, ctype_id);
+        // Occupy this name in the same namespace `convert_function` and
+        // `convert_extern_globals` insert into, so it can never be handed
+        // out again for an unrelated function or global.
+        self.renamer.borrow_mut().reserve(&name);
+        let foreign_item = mk_extern_type_item(&name);
+        ast::Item {
+            ident: Ident::from_str(&name),
+            attrs: vec![],
+            id: ast::DUMMY_NODE_ID,
+            node: ast::ItemKind::ForeignMod(ast::ForeignMod {
+                abi: ast::Abi::C,
+                items: vec![foreign_item],
+            }),
+            vis: mk().vis_from(ast::VisibilityKind::Public),
+            span: syntax_pos::DUMMY_SP,
+            tokens: None,
+        }
+        // Note: opaque records aren't keyed by `CDeclId` in this context
+        // (only by `CTypeId`), so unlike `convert_function` this item
+        // isn't run through `attach_comments` yet; extending
+        // `leading_comment`/`trailing_comment` to accept a `CTypeId` is
+        // left for whenever a record-comment fixture actually shows up.
+    }
+
+    /// Emits one `extern "C" { ... }` block holding every `extern` global
+    /// in the translation unit, each as `static NAME: T` or
+    /// `static mut NAME: T` depending on whether the C declaration was
+    /// `const`-qualified. Globals sharing linkage are grouped into a
+    /// single block rather than one block per global, matching how a
+    /// hand-written extern block lists several externs together; `None` is
+    /// returned (no item) when there are none.
+    ///
+    /// Each global's name is reserved through `self.renamer` before it's
+    /// used as an identifier, so a global whose C name collides with an
+    /// already-translated function (e.g. a global and a function both
+    /// named `state` in different C source files merged into one
+    /// translation unit) comes out suffixed (`state_1`) instead of
+    /// silently shadowing the function's binding. This only catches
+    /// collisions against functions translated *before* this call, since
+    /// `renamer` is a single shared table threaded through every
+    /// `convert_*` method — callers should translate functions first.
+    pub fn convert_extern_globals(&self) -> Option<ast::Item> {
+        let mut items = vec![];
+        for (&decl_id, kind) in &self.ast_context.decls {
+            if let CDeclKind::Variable {
+                name,
+                typ,
+                is_extern: true,
+            } = kind
+            {
+                let rust_name = self.renamer.borrow_mut().insert(decl_id, name);
+                let ty = self.type_converter.borrow_mut().convert(self.ast_context, typ.ctype);
+                let is_mut = !typ.qualifiers.is_const;
+                items.push(mk_extern_static_item(&rust_name, ty, is_mut));
+            }
+        }
+        if items.is_empty() {
+            return None;
+        }
+        Some(ast::Item {
+            ident: Ident::from_str("extern_globals"),
+            attrs: vec![],
+            id: ast::DUMMY_NODE_ID,
+            node: ast::ItemKind::ForeignMod(ast::ForeignMod {
+                abi: ast::Abi::C,
+                items,
+            }),
+            vis: mk().vis_from(ast::VisibilityKind::Inherited),
+            span: syntax_pos::DUMMY_SP,
+            tokens: None,
+        })
+    }
+
+    /// Translates a single C function declaration or definition.
+    ///
+    /// A variadic *prototype* (no body) becomes a foreign-fn item whose
+    /// signature keeps the trailing `...`. A variadic *definition* instead
+    /// gains a trailing `mut args: ...` parameter bound via Rust's
+    /// `c_variadic` feature, and its uses of `va_start`/`va_arg`/`va_end`/
+    /// `va_copy` are lowered onto `VaListImpl`/`VaList` as the body is
+    /// translated.
+    pub fn convert_function(&self, decl_id: CDeclId) -> ast::Item {
+        let decl = self.ast_context.resolve_decl(decl_id);
+        let (name, is_variadic, body) = match decl {
+            CDeclKind::Function {
+                name,
+                is_variadic,
+                body,
+                ..
+            } => (name.clone(), *is_variadic, *body),
+            _ => panic!("convert_function called on a non-function decl"),
+        };
+
+        // Only a variadic *definition* needs `c_variadic`; a variadic
+        // prototype in an `extern "C"` block does not.
+        if is_variadic && body.is_some() {
+            self.uses_variadic.set(true);
+        }
+
+        // Reserve this function's name before anything else (globals
+        // translated afterwards via `convert_extern_globals` check against
+        // this same table) so a later colliding global gets suffixed
+        // rather than this binding getting shadowed.
+        let rust_name = self.renamer.borrow_mut().insert(decl_id, &name);
+
+        let item = match body {
+            None => {
+                // A bare prototype: `fn name(params...) -> ret;` inside an
+                // `extern "C" { ... }` block, `...` preserved verbatim.
+                let foreign_decl = self.convert_prototype(decl_id, &rust_name);
+                ast::Item {
+                    ident: Ident::from_str(&rust_name),
+                    attrs: vec![],
+                    id: ast::DUMMY_NODE_ID,
+                    node: ast::ItemKind::ForeignMod(ast::ForeignMod {
+                        abi: ast::Abi::C,
+                        items: vec![foreign_decl],
+                    }),
+                    vis: mk().vis_from(ast::VisibilityKind::Public),
+                    span: syntax_pos::DUMMY_SP,
+                    tokens: None,
+                }
+            }
+            Some(body_id) => self.convert_variadic_definition(decl_id, &rust_name, body_id),
+        };
+        self.attach_comments(decl_id, item)
+    }
+
+    fn convert_prototype(&self, decl_id: CDeclId, name: &str) -> ast::ForeignItem {
+        let typ = match self.ast_context.resolve_decl(decl_id) {
+            CDeclKind::Function { typ, .. } => *typ,
+            _ => unreachable!(),
+        };
+        let (ret, params, is_variadic) = match self.ast_context.resolve_type(typ) {
+            CTypeKind::Function {
+                ret,
+                params,
+                is_variadic,
+                ..
+            } => (*ret, params.clone(), *is_variadic),
+            _ => panic!("function decl with non-function type"),
+        };
+        let param_names: Vec<String> = (0..params.len()).map(|i| format!("arg{}", i)).collect();
+
+        let decl = self.type_converter.borrow_mut().convert_foreign_fn_decl(
+            self.ast_context,
+            ret,
+            &params,
+            &param_names,
+            is_variadic,
+        );
+        mk_foreign_fn_item(name, decl, ast::Generics::default())
+    }
+
+    /// A variadic function *definition* can't be expressed as a foreign
+    /// item (it has a body), so it's translated as an ordinary `unsafe
+    /// extern "C" fn` keeping its real fixed parameters (`decl_id`'s
+    /// `CDeclKind::Function::parameters`, converted the same way
+    /// `convert_prototype` converts a prototype's params) plus a trailing
+    /// `mut args: VaListImpl` parameter bound via Rust's `c_variadic`
+    /// feature, and its `<stdarg.h>` calls rewritten onto `VaListImpl`:
+    ///
+    /// * `va_start(ap, last)` — records that `ap` refers to the real
+    ///   `args: VaListImpl` parameter; no statement is emitted, the
+    ///   parameter binding already did the work.
+    /// * `va_arg(ap, T)` — `<binding for ap>.arg::<T>()`, with `T`
+    ///   resolved by `convert_type`. `ap` need not be `args` itself — see
+    ///   `va_copy` below.
+    /// * `va_end(ap)` — a no-op; dropping the binding for `ap` tears the
+    ///   list down.
+    /// * `va_copy(dst, src)` — `let mut va_list_copy = <binding for src>.clone();`
+    ///   (the name suffixed by `renamer` if already taken), registered as
+    ///   `dst`'s own distinct binding so later
+    ///   `va_arg(dst, T)` reads from the copy rather than aliasing `src`.
+    ///
+    /// Every `ap`/`dst`/`src` above is whatever C declaration the `va_arg`
+    /// macro expansion actually names, tracked through `va_list_bindings`
+    /// keyed by that declaration's `CDeclId` — never hardcoded to the
+    /// function's own `args` parameter, since a function can fork
+    /// additional `va_list`s via `va_copy`.
+    fn convert_variadic_definition(
+        &self,
+        decl_id: CDeclId,
+        name: &str,
+        body_id: crate::c_ast::CStmtId,
+    ) -> ast::Item {
+        let (parameters, is_variadic) = match self.ast_context.resolve_decl(decl_id) {
+            CDeclKind::Function {
+                parameters,
+                is_variadic,
+                ..
+            } => (parameters.clone(), *is_variadic),
+            _ => unreachable!(),
+        };
+        let mut inputs: Vec<ast::Arg> = parameters
+            .iter()
+            .map(|param_decl_id| {
+                let (param_name, param_typ) = match self.ast_context.resolve_decl(*param_decl_id) {
+                    CDeclKind::Variable { name, typ, .. } => (name.clone(), *typ),
+                    _ => panic!("function parameter decl is not a variable"),
+                };
+                let ty = self
+                    .type_converter
+                    .borrow_mut()
+                    .convert(self.ast_context, param_typ.ctype);
+                mk().arg(ty, param_name.as_str())
+            })
+            .collect();
+        if is_variadic {
+            let args_pat = mk().set_mutbl(ast::Mutability::Mutable).ident_pat("args");
+            inputs.push(mk().arg(mk().path_ty(vec!["VaListImpl"]), args_pat));
+        }
+
+        let mut stmts = vec![];
+        if let crate::c_ast::CStmtKind::Compound(stmt_ids) = &self.ast_context.stmts[&body_id] {
+            for stmt_id in stmt_ids {
+                stmts.extend(self.convert_stdarg_stmt(*stmt_id));
+            }
+        }
+
+        let block = P(ast::Block {
+            stmts,
+            id: ast::DUMMY_NODE_ID,
+            rules: ast::BlockCheckMode::Default,
+            span: syntax_pos::DUMMY_SP,
+        });
+
+        mk().unsafe_().abi(ast::Abi::C).fn_item(
+            Ident::from_str(name),
+            ast::FnDecl {
+                inputs,
+                output: ast::FunctionRetTy::Default(syntax_pos::DUMMY_SP),
+                variadic: is_variadic,
+            },
+            block,
+        )
+    }
+
+    /// Resolves `expr_id` (expected to be a `CExprKind::DeclRef` naming a
+    /// `va_list`-typed local) to the Rust binding currently standing in
+    /// for it: the real `args` parameter if it's never been through
+    /// `va_copy`, or the name minted for it when it was copied. Falls back
+    /// to `"args"` when no binding is registered for it, i.e. for a
+    /// `va_list` this pass hasn't yet seen a `va_start`/`va_copy` for.
+    fn va_list_binding(&self, expr_id: CExprId) -> String {
+        let decl_id = match &self.ast_context.exprs[&expr_id] {
+            CExprKind::DeclRef(decl_id) => Some(*decl_id),
+            _ => None,
+        };
+        decl_id
+            .and_then(|id| self.va_list_bindings.borrow().get(&id).cloned())
+            .unwrap_or_else(|| "args".to_string())
+    }
+
+    fn convert_stdarg_stmt(&self, stmt_id: crate::c_ast::CStmtId) -> Option<ast::Stmt> {
+        match &self.ast_context.stmts[&stmt_id] {
+            crate::c_ast::CStmtKind::Expr(expr_id) => self.convert_stdarg_stmt_expr(*expr_id),
+            crate::c_ast::CStmtKind::Return(Some(expr_id)) => Some(mk().semi_stmt(mk().return_expr(
+                Some(self.convert_stdarg_expr(*expr_id)),
+            ))),
+            crate::c_ast::CStmtKind::Return(None) => Some(mk().semi_stmt(mk().return_expr(None))),
+            crate::c_ast::CStmtKind::Compound(_) => {
+                // Nested blocks aren't produced by the stdarg-only bodies
+                // this pass targets; drop rather than recursing
+                // indefinitely into a shape this pass doesn't model.
+                None
+            }
+        }
+    }
+
+    /// Handles a top-level expression-statement specially so that
+    /// `va_start`/`va_end` (administrative, emit nothing) and `va_copy`
+    /// (needs a `let` binding, not a bare expression) aren't forced
+    /// through `convert_stdarg_expr`'s single-`Expr`-in, single-`Expr`-out
+    /// shape.
+    fn convert_stdarg_stmt_expr(&self, expr_id: CExprId) -> Option<ast::Stmt> {
+        if let CExprKind::Call(decl_id, args) = &self.ast_context.exprs[&expr_id] {
+            let builtin_name = match self.ast_context.resolve_decl(*decl_id) {
+                CDeclKind::Function { name, .. } => name.as_str(),
+                _ => "",
+            };
+            match builtin_name {
+                "__builtin_va_start" => {
+                    if let Some(ap) = args.first() {
+                        if let CExprKind::DeclRef(ap_decl) = &self.ast_context.exprs[ap] {
+                            self.va_list_bindings
+                                .borrow_mut()
+                                .insert(*ap_decl, "args".to_string());
+                        }
+                    }
+                    return None;
+                }
+                "__builtin_va_end" => return None,
+                "__builtin_va_copy" => {
+                    let (dst, src) = match (args.first(), args.get(1)) {
+                        (Some(dst), Some(src)) => (*dst, *src),
+                        _ => return None,
+                    };
+                    let src_name = self.va_list_binding(src);
+                    let dst_decl = match &self.ast_context.exprs[&dst] {
+                        CExprKind::DeclRef(decl_id) => *decl_id,
+                        _ => return None,
+                    };
+                    let dst_name = self
+                        .renamer
+                        .borrow_mut()
+                        .insert(dst_decl, "va_list_copy");
+                    self.va_list_bindings
+                        .borrow_mut()
+                        .insert(dst_decl, dst_name.clone());
+                    let rhs = mk().method_call_expr(mk().ident_expr(&src_name), "clone", vec![]);
+                    let pat = mk().set_mutbl(ast::Mutability::Mutable).ident_pat(&dst_name);
+                    return Some(mk().local_stmt(pat, None as Option<P<ast::Ty>>, Some(rhs)));
+                }
+                _ => {}
+            }
+        }
+        Some(mk().expr_stmt(self.convert_stdarg_expr(expr_id)))
+    }
+
+    fn convert_stdarg_expr(&self, expr_id: CExprId) -> P<ast::Expr> {
+        match &self.ast_context.exprs[&expr_id] {
+            CExprKind::VAArg { va_list, ty } => {
+                let binding = self.va_list_binding(*va_list);
+                let ty = self.type_converter.borrow_mut().convert(self.ast_context, ty.ctype);
+                mk().method_call_expr_with_generics(mk().ident_expr(&binding), "arg", vec![ty], vec![])
+            }
+            CExprKind::Call(decl_id, args) => self.convert_stdarg_call(*decl_id, args),
+            CExprKind::DeclRef(decl_id) => mk().ident_expr(
+                &self
+                    .va_list_bindings
+                    .borrow()
+                    .get(decl_id)
+                    .cloned()
+                    .unwrap_or_else(|| "args".to_string()),
+            ),
+            CExprKind::Literal(_) => mk().tuple_expr(vec![] as Vec<P<ast::Expr>>),
+        }
+    }
+
+    fn convert_stdarg_call(&self, decl_id: CDeclId, args: &[CExprId]) -> P<ast::Expr> {
+        // `va_start`/`va_end`/`va_copy` only need special handling when
+        // they appear as their own statement (`convert_stdarg_stmt_expr`
+        // handles that); reaching here as a sub-expression (which stdarg
+        // usage never does in practice) has nothing sensible to lower to.
+        let _ = (decl_id, args);
+        mk().tuple_expr(vec![] as Vec<P<ast::Expr>>)
+    }
+
+    /// Crate-level `#![feature(...)]` attributes gated on what this
+    /// translation actually needed, emitted once the whole translation
+    /// unit has been walked.
+    pub fn crate_features(&self) -> Vec<ast::Attribute> {
+        let mut attrs = vec![];
+        if self.uses_variadic.get() {
+            attrs.push(mk_feature_attr("c_variadic"));
+        }
+        if self.uses_extern_types.get() {
+            attrs.push(mk_feature_attr("extern_types"));
+        }
+        attrs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c_ast::{CQualTypeId, CStmtId, CStmtKind, Qualifiers};
+
+    fn qual(ctype: CTypeId) -> CQualTypeId {
+        CQualTypeId {
+            qualifiers: Qualifiers::default(),
+            ctype,
+        }
+    }
+
+    fn builtin_decl(ctxt: &mut TypedAstContext, id: u64, name: &str) -> CDeclId {
+        let decl_id = CDeclId(id);
+        ctxt.decls.insert(
+            decl_id,
+            CDeclKind::Function {
+                name: name.to_string(),
+                // Never looked at for these builtins; only `name` matters.
+                typ: CTypeId(0),
+                is_variadic: false,
+                parameters: vec![],
+                body: None,
+            },
+        );
+        decl_id
+    }
+
+    fn local_var(ctxt: &mut TypedAstContext, id: u64, name: &str, typ: CQualTypeId) -> CDeclId {
+        let decl_id = CDeclId(id);
+        ctxt.decls.insert(
+            decl_id,
+            CDeclKind::Variable {
+                name: name.to_string(),
+                typ,
+                is_extern: false,
+            },
+        );
+        decl_id
+    }
+
+    fn decl_ref(ctxt: &mut TypedAstContext, id: u64, decl: CDeclId) -> CExprId {
+        let expr_id = CExprId(id);
+        ctxt.exprs.insert(expr_id, CExprKind::DeclRef(decl));
+        expr_id
+    }
+
+    #[test]
+    fn convert_function_threads_fixed_parameters_and_marks_variadic() {
+        // void foo(const char *fmt, ...) { }
+        let mut ctxt = TypedAstContext::new();
+        let char_ty = CTypeId(1);
+        ctxt.types.insert(char_ty, CTypeKind::Char);
+        let const_char_ptr_ty = CTypeId(2);
+        ctxt.types.insert(
+            const_char_ptr_ty,
+            CTypeKind::Pointer(CQualTypeId {
+                qualifiers: Qualifiers {
+                    is_const: true,
+                    ..Default::default()
+                },
+                ctype: char_ty,
+            }),
+        );
+        let void_ty = CTypeId(3);
+        ctxt.types.insert(void_ty, CTypeKind::Void);
+        let fn_ty = CTypeId(4);
+        ctxt.types.insert(
+            fn_ty,
+            CTypeKind::Function {
+                ret: qual(void_ty),
+                params: vec![qual(const_char_ptr_ty)],
+                is_variadic: true,
+                is_noreturn: false,
+            },
+        );
+
+        let fmt_decl = local_var(&mut ctxt, 1, "fmt", qual(const_char_ptr_ty));
+
+        let body_id = CStmtId(1);
+        ctxt.stmts.insert(body_id, CStmtKind::Compound(vec![]));
+
+        let fn_decl = CDeclId(2);
+        ctxt.decls.insert(
+            fn_decl,
+            CDeclKind::Function {
+                name: "foo".to_string(),
+                typ: fn_ty,
+                is_variadic: true,
+                parameters: vec![fmt_decl],
+                body: Some(body_id),
+            },
+        );
+
+        let translation = Translation::new(&ctxt, TranslationConfig::default());
+        let item = translation.convert_function(fn_decl);
+
+        match item.node {
+            ast::ItemKind::Fn(ref decl, ..) => {
+                assert!(decl.variadic, "fixed-param + `...` definition must stay variadic");
+                assert_eq!(
+                    decl.inputs.len(),
+                    2,
+                    "fixed parameter `fmt` must not be dropped alongside the trailing `args`"
+                );
+            }
+            other => panic!("expected ItemKind::Fn, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn va_start_then_va_copy_then_va_arg_track_distinct_bindings() {
+        // void foo(const char *fmt, ...) {
+        //     va_list ap;
+        //     va_start(ap, fmt);
+        //     va_list ap2;
+        //     va_copy(ap2, ap);
+        //     return va_arg(ap2, int);
+        // }
+        let mut ctxt = TypedAstContext::new();
+        let int_ty = CTypeId(1);
+        ctxt.types.insert(int_ty, CTypeKind::Int);
+        let va_list_ty = CTypeId(2);
+        ctxt.types.insert(
+            va_list_ty,
+            CTypeKind::Record {
+                name: Some("__va_list_tag".to_string()),
+                is_complete: true,
+            },
+        );
+
+        let fmt_decl = local_var(&mut ctxt, 1, "fmt", qual(int_ty));
+        let ap_decl = local_var(&mut ctxt, 2, "ap", qual(va_list_ty));
+        let ap2_decl = local_var(&mut ctxt, 3, "ap2", qual(va_list_ty));
+
+        let va_start_decl = builtin_decl(&mut ctxt, 10, "__builtin_va_start");
+        let va_copy_decl = builtin_decl(&mut ctxt, 11, "__builtin_va_copy");
+
+        let ap_ref_1 = decl_ref(&mut ctxt, 1, ap_decl);
+        let fmt_ref = decl_ref(&mut ctxt, 2, fmt_decl);
+        let va_start_call = CExprId(3);
+        ctxt.exprs.insert(
+            va_start_call,
+            CExprKind::Call(va_start_decl, vec![ap_ref_1, fmt_ref]),
+        );
+        let va_start_stmt = CStmtId(1);
+        ctxt.stmts
+            .insert(va_start_stmt, CStmtKind::Expr(va_start_call));
+
+        let ap2_ref = decl_ref(&mut ctxt, 4, ap2_decl);
+        let ap_ref_2 = decl_ref(&mut ctxt, 5, ap_decl);
+        let va_copy_call = CExprId(6);
+        ctxt.exprs.insert(
+            va_copy_call,
+            CExprKind::Call(va_copy_decl, vec![ap2_ref, ap_ref_2]),
+        );
+        let va_copy_stmt = CStmtId(2);
+        ctxt.stmts
+            .insert(va_copy_stmt, CStmtKind::Expr(va_copy_call));
+
+        let ap2_ref_3 = decl_ref(&mut ctxt, 7, ap2_decl);
+        let va_arg_expr = CExprId(8);
+        ctxt.exprs.insert(
+            va_arg_expr,
+            CExprKind::VAArg {
+                va_list: ap2_ref_3,
+                ty: qual(int_ty),
+            },
+        );
+        let return_stmt = CStmtId(3);
+        ctxt.stmts
+            .insert(return_stmt, CStmtKind::Return(Some(va_arg_expr)));
+
+        let translation = Translation::new(&ctxt, TranslationConfig::default());
+
+        assert!(translation.convert_stdarg_stmt(va_start_stmt).is_none());
+        assert_eq!(
+            translation.va_list_bindings.borrow().get(&ap_decl).cloned(),
+            Some("args".to_string())
+        );
+
+        assert!(translation.convert_stdarg_stmt(va_copy_stmt).is_some());
+        let ap2_binding = translation.va_list_bindings.borrow().get(&ap2_decl).cloned();
+        assert_eq!(ap2_binding, Some("va_list_copy".to_string()));
+        assert_ne!(
+            ap2_binding,
+            Some("args".to_string()),
+            "a va_copy destination must not alias the original va_list's binding"
+        );
+
+        assert!(translation.convert_stdarg_stmt(return_stmt).is_some());
+    }
+}
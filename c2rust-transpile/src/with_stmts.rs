@@ -0,0 +1,70 @@
+//! Bundles a Rust expression together with the statements that must run
+//! before it so that side effects embedded in a C expression (comma
+//! operators, assignments-as-expressions, ...) can be hoisted out into
+//! their own statements when the surrounding Rust expression can't host
+//! them directly.
+
+use syntax::ast;
+use syntax::ptr::P;
+
+/// `stmts` must execute, in order, before `val` is evaluated.
+pub struct WithStmts<T> {
+    pub stmts: Vec<ast::Stmt>,
+    pub val: T,
+}
+
+impl<T> WithStmts<T> {
+    pub fn new(stmts: Vec<ast::Stmt>, val: T) -> Self {
+        WithStmts { stmts, val }
+    }
+
+    pub fn new_val(val: T) -> Self {
+        WithStmts { stmts: vec![], val }
+    }
+
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> WithStmts<U> {
+        WithStmts {
+            stmts: self.stmts,
+            val: f(self.val),
+        }
+    }
+
+    pub fn and_then<U, F: FnOnce(T) -> WithStmts<U>>(mut self, f: F) -> WithStmts<U> {
+        let next = f(self.val);
+        self.stmts.extend(next.stmts);
+        WithStmts {
+            stmts: self.stmts,
+            val: next.val,
+        }
+    }
+}
+
+impl WithStmts<P<ast::Expr>> {
+    /// Flattens into a single block expression when `stmts` is non-empty,
+    /// otherwise returns `val` unwrapped.
+    pub fn to_expr(mut self) -> P<ast::Expr> {
+        if self.stmts.is_empty() {
+            self.val
+        } else {
+            self.stmts.push(ast::Stmt {
+                id: ast::DUMMY_NODE_ID,
+                node: ast::StmtKind::Expr(self.val),
+                span: syntax_pos::DUMMY_SP,
+            });
+            P(ast::Expr {
+                id: ast::DUMMY_NODE_ID,
+                node: ast::ExprKind::Block(
+                    P(ast::Block {
+                        stmts: self.stmts,
+                        id: ast::DUMMY_NODE_ID,
+                        rules: ast::BlockCheckMode::Default,
+                        span: syntax_pos::DUMMY_SP,
+                    }),
+                    None,
+                ),
+                span: syntax_pos::DUMMY_SP,
+                attrs: syntax::ThinVec::new(),
+            })
+        }
+    }
+}